@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// `key=value`, one per line, under the Tauri app config dir. Intentionally
+/// simple: the UI persists a handful of scalar settings (e.g. `autostart`,
+/// `default_board`) rather than a structured document.
+const CONFIG_FILE_NAME: &str = "pulseblaster.conf";
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create config dir: {}", e))?;
+    Ok(dir.join(CONFIG_FILE_NAME))
+}
+
+fn read_all(app: &AppHandle) -> Result<HashMap<String, String>, String> {
+    let path = config_path(app)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read config file: {}", e))?;
+
+    Ok(contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect())
+}
+
+fn write_all(app: &AppHandle, values: &HashMap<String, String>) -> Result<(), String> {
+    let path = config_path(app)?;
+    let contents = values
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    fs::write(&path, contents).map_err(|e| format!("Failed to write config file: {}", e))
+}
+
+/// Read a single key, for callers (like `PulseBlaster`) that don't need the
+/// whole config document.
+pub fn get(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    Ok(read_all(app)?.get(key).cloned())
+}
+
+#[tauri::command]
+pub fn get_config(key: String, app: AppHandle) -> Result<Option<String>, String> {
+    get(&app, &key)
+}
+
+#[tauri::command]
+pub fn set_config(key: String, value: String, app: AppHandle) -> Result<(), String> {
+    let mut values = read_all(&app)?;
+    values.insert(key, value);
+    write_all(&app, &values)
+}
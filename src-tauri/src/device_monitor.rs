@@ -0,0 +1,105 @@
+use crate::python_bridge;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the background thread re-queries the CLI for attached boards.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardInfo {
+    pub board: i32,
+    pub serial: String,
+    pub firmware: String,
+    #[serde(rename = "core_clock_MHz")]
+    pub core_clock_mhz: f64,
+}
+
+/// Last-known set of attached boards, keyed by board id.
+pub struct DeviceMonitorState(pub Mutex<HashMap<i32, BoardInfo>>);
+
+impl Default for DeviceMonitorState {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+#[tauri::command]
+pub async fn enumerate_boards(app: AppHandle) -> Result<Vec<BoardInfo>, String> {
+    let boards = query_boards(&app).await?;
+
+    let state = app.state::<DeviceMonitorState>();
+    let mut known = state.0.lock().unwrap();
+    *known = boards.iter().cloned().map(|b| (b.board, b)).collect();
+
+    Ok(boards)
+}
+
+async fn query_boards(app: &AppHandle) -> Result<Vec<BoardInfo>, String> {
+    // `stream: false` — this is a background poll, not a user-initiated
+    // command, so it shouldn't surface as a `pb-output` event. The request
+    // id is only used for that (and cancellation) gated behind `stream`, so
+    // a fresh one-off id is fine here; nothing correlates against it.
+    let output =
+        python_bridge::run_cli(app, &python_bridge::next_request_id(), "list", None, false)
+            .await?;
+    serde_json::from_str(&output).map_err(|e| format!("Failed to parse board list: {}", e))
+}
+
+/// Once this many consecutive polls have failed, back off to
+/// `MAX_POLL_INTERVAL` and stop logging every failure — otherwise a
+/// permanently missing `pulseblaster.exe` fills the log with a warning
+/// every `POLL_INTERVAL`, forever.
+const BACKOFF_THRESHOLD: u32 = 3;
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Spawn the background task that polls for PulseBlaster boards arriving
+/// and departing, emitting `board-connected` / `board-disconnected` events
+/// to the webview as the set changes.
+pub fn spawn_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut consecutive_failures: u32 = 0;
+
+        loop {
+            match query_boards(&app).await {
+                Ok(boards) => {
+                    consecutive_failures = 0;
+
+                    let fresh: HashMap<i32, BoardInfo> =
+                        boards.iter().cloned().map(|b| (b.board, b)).collect();
+
+                    let state = app.state::<DeviceMonitorState>();
+                    let mut known = state.0.lock().unwrap();
+
+                    for (id, info) in fresh.iter() {
+                        if !known.contains_key(id) {
+                            let _ = app.emit("board-connected", info);
+                        }
+                    }
+                    for (id, info) in known.iter() {
+                        if !fresh.contains_key(id) {
+                            let _ = app.emit("board-disconnected", info);
+                        }
+                    }
+
+                    *known = fresh;
+                }
+                Err(err) => {
+                    consecutive_failures = consecutive_failures.saturating_add(1);
+                    if consecutive_failures <= BACKOFF_THRESHOLD {
+                        log::warn!("Board enumeration failed: {}", err);
+                    }
+                }
+            }
+
+            let interval = if consecutive_failures >= BACKOFF_THRESHOLD {
+                MAX_POLL_INTERVAL
+            } else {
+                POLL_INTERVAL
+            };
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
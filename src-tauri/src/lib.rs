@@ -1,88 +1,180 @@
+mod config_store;
+mod device_monitor;
+mod program_validator;
 mod python_bridge;
 
-use python_bridge::{PBInstruction, PulseBlaster, PulseBlasterConfig};
-use std::sync::Mutex;
-use tauri::State;
+use device_monitor::DeviceMonitorState;
+use program_validator::ProgramError;
+use python_bridge::{ActiveCommands, PBInstruction, ProgramHandle, PulseBlaster, PulseBlasterConfig};
+use std::sync::Arc;
+use tauri::{Emitter, State};
+use tokio::sync::Mutex;
 
-// Global state to hold Python PulseBlaster instance
-struct PulseBlasterState(Mutex<Option<PulseBlaster>>);
+// Global state to hold the PulseBlaster instance. Wrapped in an `Arc` so
+// command handlers can clone a handle to it and release the state lock
+// before making a long-running CLI call — holding the lock across an
+// `.await` would block every other command (e.g. `stop`) behind whichever
+// one is currently in flight.
+struct PulseBlasterState(Mutex<Option<Arc<PulseBlaster>>>);
+
+/// Clone the current `PulseBlaster` handle out of state without holding the
+/// lock any longer than the clone itself takes.
+async fn current_pulseblaster(state: &State<'_, PulseBlasterState>) -> Result<Arc<PulseBlaster>, String> {
+    state
+        .0
+        .lock()
+        .await
+        .clone()
+        .ok_or_else(|| "PulseBlaster not initialized".to_string())
+}
+
+/// Run the operation built by `make_operation` in the background and return
+/// its request id immediately instead of making the frontend wait on the
+/// whole round trip. The outcome is reported later via a `pb-complete` event
+/// (`{ requestId, ok, result }` or `{ requestId, ok: false, error }`).
+///
+/// The id is minted *before* `make_operation` runs and handed to it, so it
+/// can be threaded down into `run_cli` — that way `pb-started`, `pb-output`,
+/// `pb-complete`, and the `ActiveCommands` cancellation key all share the
+/// one id the frontend actually received, instead of each layer minting its
+/// own.
+fn spawn_tracked<F, Fut, T>(app: tauri::AppHandle, make_operation: F) -> String
+where
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>> + Send + 'static,
+    T: serde::Serialize + Send + 'static,
+{
+    let request_id = python_bridge::next_request_id();
+    let operation = make_operation(request_id.clone());
+    let emit_id = request_id.clone();
+
+    // Run `operation` on its own task so a panic deep in the CLI path (e.g. a
+    // poisoned mutex) can't strand the frontend waiting on a `pb-complete`
+    // that never arrives; a panic there is instead reported as a failed
+    // outcome by whichever task awaits this one's `JoinHandle`.
+    let task = tauri::async_runtime::spawn(operation);
+
+    tauri::async_runtime::spawn(async move {
+        let outcome = task
+            .await
+            .unwrap_or_else(|join_err| Err(format!("PulseBlaster task panicked: {}", join_err)));
+
+        let payload = match outcome {
+            Ok(result) => serde_json::json!({ "requestId": emit_id, "ok": true, "result": result }),
+            Err(error) => serde_json::json!({ "requestId": emit_id, "ok": false, "error": error }),
+        };
+        let _ = app.emit("pb-complete", &payload);
+    });
+
+    request_id
+}
 
 #[tauri::command]
-fn initialize_pulseblaster(
+async fn initialize_pulseblaster(
     config: PulseBlasterConfig,
-    state: State<PulseBlasterState>,
+    state: State<'_, PulseBlasterState>,
     app: tauri::AppHandle,
 ) -> Result<String, String> {
     let pb = PulseBlaster::new(config, app);
-    let result = pb.initialize()?;
+    let result = pb.initialize().await?;
 
-    let mut pb_state = state.0.lock().unwrap();
-    *pb_state = Some(pb);
+    let mut pb_state = state.0.lock().await;
+    *pb_state = Some(Arc::new(pb));
 
     Ok(result)
 }
 
 #[tauri::command]
-fn program_instructions(
+async fn program_instructions(
     instructions: Vec<PBInstruction>,
-    state: State<PulseBlasterState>,
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
 ) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.program_instructions(instructions)
-    } else {
-        Err("PulseBlaster not initialized".to_string())
-    }
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked::<_, _, ProgramHandle>(app, |request_id| async move {
+        pb.program_instructions(instructions, &request_id).await
+    }))
 }
 
 #[tauri::command]
-fn start_pulseblaster(state: State<PulseBlasterState>) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.start()
-    } else {
-        Err("PulseBlaster not initialized".to_string())
-    }
+async fn replay_program(
+    handle: String,
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.replay_program(&handle, &request_id).await
+    }))
 }
 
 #[tauri::command]
-fn stop_pulseblaster(state: State<PulseBlasterState>) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.stop()
-    } else {
-        Err("PulseBlaster not initialized".to_string())
-    }
+async fn start_pulseblaster(
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.start(&request_id).await
+    }))
 }
 
 #[tauri::command]
-fn reset_pulseblaster(state: State<PulseBlasterState>) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.reset()
-    } else {
-        Err("PulseBlaster not initialized".to_string())
-    }
+async fn stop_pulseblaster(
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.stop(&request_id).await
+    }))
 }
 
 #[tauri::command]
-fn get_pulseblaster_status(state: State<PulseBlasterState>) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.get_status()
-    } else {
-        Err("PulseBlaster not initialized".to_string())
-    }
+async fn reset_pulseblaster(
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.reset(&request_id).await
+    }))
 }
 
 #[tauri::command]
-fn wait_until_stopped(timeout_s: f64, state: State<PulseBlasterState>) -> Result<String, String> {
-    let pb_state = state.0.lock().unwrap();
-    if let Some(ref pb) = *pb_state {
-        pb.wait_until_stopped(timeout_s)
-    } else {
-        Err("Python PulseBlaster not initialized".to_string())
-    }
+async fn get_pulseblaster_status(
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.get_status(&request_id).await
+    }))
+}
+
+#[tauri::command]
+async fn wait_until_stopped(
+    timeout_s: f64,
+    state: State<'_, PulseBlasterState>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let pb = current_pulseblaster(&state).await?;
+    Ok(spawn_tracked(app, |request_id| async move {
+        pb.wait_until_stopped(timeout_s, &request_id).await
+    }))
+}
+
+#[tauri::command]
+fn cancel_command(request_id: String, app: tauri::AppHandle) -> Result<(), String> {
+    python_bridge::cancel_command(&app, &request_id)
+}
+
+#[tauri::command]
+fn validate_program(
+    instructions: Vec<PBInstruction>,
+    core_clock_mhz: Option<f64>,
+) -> Result<(), Vec<ProgramError>> {
+    program_validator::validate_program(&instructions, core_clock_mhz)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -90,6 +182,8 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(PulseBlasterState(Mutex::new(None)))
+        .manage(DeviceMonitorState::default())
+        .manage(ActiveCommands::default())
         .invoke_handler(tauri::generate_handler![
             initialize_pulseblaster,
             start_pulseblaster,
@@ -97,7 +191,13 @@ pub fn run() {
             reset_pulseblaster,
             get_pulseblaster_status,
             wait_until_stopped,
-            program_instructions
+            program_instructions,
+            replay_program,
+            cancel_command,
+            validate_program,
+            device_monitor::enumerate_boards,
+            config_store::get_config,
+            config_store::set_config
         ])
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -107,6 +207,9 @@ pub fn run() {
                         .build(),
                 )?;
             }
+
+            device_monitor::spawn_monitor(app.handle().clone());
+
             Ok(())
         })
         .run(tauri::generate_context!())
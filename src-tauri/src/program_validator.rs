@@ -0,0 +1,182 @@
+use crate::python_bridge::PBInstruction;
+use serde::Serialize;
+
+/// Minimum instruction duration the hardware can actually produce a pulse
+/// for; anything shorter is rounded away by the board.
+const MIN_PULSE_WIDTH_NS: f64 = 5.0;
+
+/// Largest delay a single instruction can encode before a `LONG_DELAY`
+/// (which multiplies its duration by its `data` field) is required instead.
+const MAX_INSTRUCTION_NS: f64 = 859_000_000.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramError {
+    pub index: usize,
+    pub message: String,
+}
+
+/// Validate the structural and timing rules of a pulse program before it's
+/// handed to the CLI, where a violation currently just fails opaquely on the
+/// hardware. Checks every `LOOP`/`END_LOOP` pair, `JSR`/`RTS` subroutine
+/// structure, in-range branch targets, and per-instruction timing.
+pub fn validate_program(
+    instructions: &[PBInstruction],
+    core_clock_mhz: Option<f64>,
+) -> Result<(), Vec<ProgramError>> {
+    let mut errors = Vec::new();
+
+    check_loops(instructions, &mut errors);
+    check_subroutines(instructions, &mut errors);
+    check_branch_targets(instructions, &mut errors);
+    check_timing(instructions, core_clock_mhz, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn check_loops(instructions: &[PBInstruction], errors: &mut Vec<ProgramError>) {
+    let mut open_loops: Vec<usize> = Vec::new();
+
+    for (index, instruction) in instructions.iter().enumerate() {
+        match instruction.opcode.as_str() {
+            "LOOP" => open_loops.push(index),
+            "END_LOOP" => match open_loops.pop() {
+                Some(loop_start) => {
+                    if instruction.data as usize != loop_start {
+                        errors.push(ProgramError {
+                            index,
+                            message: format!(
+                                "END_LOOP references instruction {} but the innermost open LOOP is at {} (crossed loop pair)",
+                                instruction.data, loop_start
+                            ),
+                        });
+                    }
+                }
+                None => errors.push(ProgramError {
+                    index,
+                    message: "END_LOOP has no matching LOOP".to_string(),
+                }),
+            },
+            _ => {}
+        }
+    }
+
+    for loop_start in open_loops {
+        errors.push(ProgramError {
+            index: loop_start,
+            message: "LOOP is never closed by a matching END_LOOP".to_string(),
+        });
+    }
+}
+
+fn check_subroutines(instructions: &[PBInstruction], errors: &mut Vec<ProgramError>) {
+    for (index, instruction) in instructions.iter().enumerate() {
+        if instruction.opcode != "JSR" {
+            continue;
+        }
+
+        let target = instruction.data;
+        if target < 0 || target as usize >= instructions.len() {
+            // Out-of-range targets are reported by `check_branch_targets`.
+            continue;
+        }
+
+        // Stop at the first control-terminating instruction reached from
+        // `target` (an unrelated `RTS` further down the program, belonging
+        // to a different subroutine, must not count) — the subroutine is
+        // only well-formed if that terminator is itself an `RTS`.
+        let reaches_rts = instructions[target as usize..]
+            .iter()
+            .find(|inst| matches!(inst.opcode.as_str(), "RTS" | "BRANCH" | "STOP"))
+            .is_some_and(|inst| inst.opcode == "RTS");
+
+        if !reaches_rts {
+            errors.push(ProgramError {
+                index,
+                message: format!(
+                    "subroutine starting at instruction {} is never terminated by RTS",
+                    target
+                ),
+            });
+        }
+    }
+}
+
+fn check_branch_targets(instructions: &[PBInstruction], errors: &mut Vec<ProgramError>) {
+    for (index, instruction) in instructions.iter().enumerate() {
+        let references_index = matches!(instruction.opcode.as_str(), "BRANCH" | "END_LOOP" | "JSR");
+        if !references_index {
+            continue;
+        }
+
+        if instruction.data < 0 || instruction.data as usize >= instructions.len() {
+            errors.push(ProgramError {
+                index,
+                message: format!(
+                    "{} targets instruction {}, which is out of range (program has {} instructions)",
+                    instruction.opcode,
+                    instruction.data,
+                    instructions.len()
+                ),
+            });
+        }
+    }
+}
+
+fn check_timing(
+    instructions: &[PBInstruction],
+    core_clock_mhz: Option<f64>,
+    errors: &mut Vec<ProgramError>,
+) {
+    for (index, instruction) in instructions.iter().enumerate() {
+        let duration_ns = match duration_to_ns(instruction.duration, &instruction.units, core_clock_mhz) {
+            Ok(ns) => ns,
+            Err(message) => {
+                errors.push(ProgramError { index, message });
+                continue;
+            }
+        };
+
+        if duration_ns < MIN_PULSE_WIDTH_NS {
+            errors.push(ProgramError {
+                index,
+                message: format!(
+                    "duration {:.2}ns is below the minimum pulse width of {}ns",
+                    duration_ns, MIN_PULSE_WIDTH_NS
+                ),
+            });
+        }
+
+        if duration_ns > MAX_INSTRUCTION_NS && instruction.opcode != "LONG_DELAY" {
+            errors.push(ProgramError {
+                index,
+                message: format!(
+                    "duration {:.2}ns exceeds the single-instruction max of {}ns; use LONG_DELAY instead",
+                    duration_ns, MAX_INSTRUCTION_NS
+                ),
+            });
+        }
+    }
+}
+
+/// Convert a `(duration, units)` pair into nanoseconds. Shared with
+/// `python_bridge`'s duration normalization step, which re-expresses every
+/// instruction's duration in clock ticks once a clock source is configured.
+pub fn duration_to_ns(duration: f64, units: &str, core_clock_mhz: Option<f64>) -> Result<f64, String> {
+    match units {
+        "ns" => Ok(duration),
+        "us" => Ok(duration * 1_000.0),
+        "ms" => Ok(duration * 1_000_000.0),
+        "s" => Ok(duration * 1_000_000_000.0),
+        "clk" => {
+            let clock_mhz = core_clock_mhz.ok_or_else(|| {
+                "duration is in \"clk\" units but no core_clock_MHz is configured".to_string()
+            })?;
+            Ok(duration * (1_000.0 / clock_mhz))
+        }
+        other => Err(format!("unknown duration unit \"{}\"", other)),
+    }
+}
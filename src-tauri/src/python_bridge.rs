@@ -1,7 +1,12 @@
+use crate::program_validator;
 use serde::{Deserialize, Serialize};
-use std::io::Write;
-use std::process::{Command, Stdio};
-use tauri::Manager;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PulseBlasterConfig {
@@ -9,9 +14,42 @@ pub struct PulseBlasterConfig {
     #[serde(rename = "core_clock_MHz")]
     pub core_clock_mhz: Option<f64>,
     pub debug: bool,
+    /// A pulse program to load (and optionally start) automatically once
+    /// `initialize_pulseblaster` confirms the board is reachable.
+    pub startup_program: Option<StartupProgram>,
+    /// Which clock drives timing. When set, `program_instructions` rewrites
+    /// every instruction's duration into ticks of this clock before sending
+    /// the program to the CLI, instead of leaving unit conversion to it.
+    pub clock_source: Option<ClockSource>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Mirrors selecting between a PulseBlaster's internal oscillator and an
+/// external reference fed into its clock input ("bypass" mode).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClockSource {
+    Internal { mhz: f64 },
+    ExternalBypass { mhz: f64 },
+}
+
+impl ClockSource {
+    pub fn mhz(&self) -> f64 {
+        match *self {
+            ClockSource::Internal { mhz } | ClockSource::ExternalBypass { mhz } => mhz,
+        }
+    }
+}
+
+/// Either the sequence itself, or a path to a saved `.json` sequence,
+/// resolved relative to the app's config dir when not absolute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum StartupProgram {
+    Inline(Vec<PBInstruction>),
+    Path(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PBInstruction {
     pub flags: Flags,
     pub opcode: String,
@@ -31,7 +69,7 @@ pub struct PBInstruction {
     pub phase_reset1: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Flags {
     Integer(u32),
@@ -39,9 +77,220 @@ pub enum Flags {
     Array(Vec<u32>),
 }
 
+/// Result of [`PulseBlaster::program_instructions`]: a content-addressed
+/// handle for the sequence that was (or would have been) loaded, and whether
+/// the board already had it loaded and programming was skipped.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramHandle {
+    pub handle: String,
+    pub reused: bool,
+}
+
 pub struct PulseBlaster {
     config: PulseBlasterConfig,
     app_handle: tauri::AppHandle,
+    /// Content hash of the instruction sequence currently loaded on the
+    /// board, if any. Used to skip redundant reprogramming.
+    loaded_handle: Mutex<Option<String>>,
+}
+
+/// An in-flight, cancellable operation: either a CLI child process (killed
+/// outright) or a pure-Rust polling loop (e.g. `wait_until_stopped`) that
+/// cooperatively checks a flag and unwinds on its own.
+pub enum ActiveCommand {
+    Process(CommandChild),
+    Cancellable(Arc<AtomicBool>),
+}
+
+/// In-flight operations, keyed by the *same* request id that was handed
+/// back to the frontend when the command was kicked off (see
+/// `lib.rs::spawn_tracked`), so a caller can cancel a command that's still
+/// running (e.g. a `run` or `wait_until_stopped` that's taking too long).
+#[derive(Default)]
+pub struct ActiveCommands(Mutex<HashMap<String, ActiveCommand>>);
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Allocate a request id. Used by `lib.rs::spawn_tracked` for commands that
+/// hand a request id back to the frontend immediately and report their
+/// outcome later via a `pb-complete` event; that same id is threaded down
+/// into [`run_cli`] so `pb-started`, `pb-output`, and `ActiveCommands` all
+/// key off the one id the frontend actually has.
+pub(crate) fn next_request_id() -> String {
+    format!("req-{}", NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Cancel an operation that was previously registered in `ActiveCommands`
+/// under `request_id` (a CLI invocation via [`run_cli`], or a polling loop
+/// like `wait_until_stopped`).
+pub fn cancel_command(app_handle: &tauri::AppHandle, request_id: &str) -> Result<(), String> {
+    let state = app_handle.state::<ActiveCommands>();
+    let mut active = state.0.lock().unwrap();
+    match active.remove(request_id) {
+        Some(ActiveCommand::Process(child)) => child
+            .kill()
+            .map_err(|e| format!("Failed to cancel command: {}", e)),
+        Some(ActiveCommand::Cancellable(flag)) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err(format!("No command in flight with id {}", request_id)),
+    }
+}
+
+/// Invoke the `pulseblaster.exe` CLI with a subcommand and an optional JSON
+/// payload written to its stdin. Shared by `PulseBlaster` and any other
+/// subsystem (e.g. the device monitor) that needs to talk to the CLI without
+/// owning a full `PulseBlasterConfig`.
+///
+/// Runs on top of `tauri_plugin_shell`'s async child API so the command
+/// thread is never blocked. When `stream` is true (user-initiated commands),
+/// stdout is forwarded line-by-line as `pb-output` events as the child
+/// produces it, and the invocation is registered in `ActiveCommands` so it
+/// can be cancelled. Background polling (the device monitor's `list`, or a
+/// `wait_until_stopped` status check) passes `stream: false` so it doesn't
+/// spam the output stream or occupy a cancellation slot for every poll.
+///
+/// `request_id` is the id the frontend was already handed (by
+/// `lib.rs::spawn_tracked`, or freshly minted for an internal-only call) and
+/// is reused verbatim for `pb-started`, `pb-output`, and the
+/// `ActiveCommands` key, so every event a caller sees for one command shares
+/// one id.
+pub async fn run_cli(
+    app_handle: &tauri::AppHandle,
+    request_id: &str,
+    command: &str,
+    payload: Option<serde_json::Value>,
+    stream: bool,
+) -> Result<String, String> {
+    let exe_path = resolve_executable_path(app_handle)?;
+
+    let (mut rx, mut child) = app_handle
+        .shell()
+        .command(exe_path)
+        .arg(command)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn process: {}", e))?;
+
+    if let Some(payload) = payload {
+        // pulseblaster.exe reads one line of JSON per invocation rather than
+        // to EOF, so a trailing newline signals "payload complete" — the
+        // shell plugin's CommandChild has no API to close stdin outright
+        // (unlike `std::process::Child::wait_with_output`, which the
+        // original synchronous implementation relied on for that).
+        let payload_line = format!("{}\n", serde_json::to_string(&payload)
+            .map_err(|e| format!("Failed to serialize payload: {}", e))?);
+        child
+            .write(payload_line.as_bytes())
+            .map_err(|e| format!("Failed to write to stdin: {}", e))?;
+    }
+
+    if stream {
+        let _ = app_handle.emit("pb-started", request_id);
+        let state = app_handle.state::<ActiveCommands>();
+        state
+            .0
+            .lock()
+            .unwrap()
+            .insert(request_id.to_string(), ActiveCommand::Process(child));
+    }
+
+    let mut stdout_lines = Vec::new();
+    let mut stderr_lines = Vec::new();
+    let mut exit_success = false;
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            CommandEvent::Stdout(line) => {
+                let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                if stream {
+                    // Carry the request id so a frontend with more than one
+                    // command in flight (now possible since commands return
+                    // before the CLI finishes) can attribute each line to
+                    // the invocation that produced it.
+                    let _ = app_handle.emit(
+                        "pb-output",
+                        &serde_json::json!({ "requestId": request_id, "line": line }),
+                    );
+                }
+                stdout_lines.push(line);
+            }
+            CommandEvent::Stderr(line) => {
+                stderr_lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+            }
+            CommandEvent::Error(err) => {
+                if stream {
+                    let state = app_handle.state::<ActiveCommands>();
+                    state.0.lock().unwrap().remove(request_id);
+                }
+                return Err(format!("Command error: {}", err));
+            }
+            CommandEvent::Terminated(payload) => {
+                exit_success = payload.code == Some(0);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if stream {
+        let state = app_handle.state::<ActiveCommands>();
+        state.0.lock().unwrap().remove(request_id);
+    }
+
+    if exit_success {
+        Ok(stdout_lines.join("\n"))
+    } else {
+        Err(format!("Command failed: {}", stderr_lines.join("\n")))
+    }
+}
+
+fn resolve_executable_path(app_handle: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let exe_name = "pulseblaster.exe";
+
+    // 1. Try Tauri resource resolution
+    if let Ok(resource_path) = app_handle
+        .path()
+        .resolve(&format!("bin/{}", exe_name), tauri::path::BaseDirectory::Resource)
+    {
+        if resource_path.exists() {
+            return Ok(resource_path);
+        }
+    }
+
+    // 2. Try relative to current executable (for bundled applications)
+    if let Ok(current_exe) = std::env::current_exe() {
+        if let Some(exe_dir) = current_exe.parent() {
+            let relative_path = exe_dir.join("bin").join(exe_name);
+            if relative_path.exists() {
+                return Ok(relative_path);
+            }
+
+            // Also try in the same directory as the main executable
+            let same_dir_path = exe_dir.join(exe_name);
+            if same_dir_path.exists() {
+                return Ok(same_dir_path);
+            }
+        }
+    }
+
+    // 3. Try from src-tauri directory (development)
+    let src_tauri_path = std::path::Path::new("src-tauri")
+        .join("bin")
+        .join(exe_name);
+    if src_tauri_path.exists() {
+        return Ok(src_tauri_path);
+    }
+
+    // 4. Try from current working directory + src-tauri (for when running from project root)
+    if let Ok(current_dir) = std::env::current_dir() {
+        let cwd_path = current_dir.join("src-tauri").join("bin").join(exe_name);
+        if cwd_path.exists() {
+            return Ok(cwd_path);
+        }
+    }
+
+    Err("Could not locate pulseblaster.exe executable".to_string())
 }
 
 impl PulseBlaster {
@@ -49,143 +298,328 @@ impl PulseBlaster {
         Self {
             config,
             app_handle,
+            loaded_handle: Mutex::new(None),
         }
     }
 
-    pub fn execute_cli_command(
+    pub async fn execute_cli_command(
         &self,
+        request_id: &str,
         command: &str,
         payload: Option<serde_json::Value>,
     ) -> Result<String, String> {
-        // Use direct execution for now since sidecar API is async and requires more setup
-        self.try_direct_execution(command, payload)
+        run_cli(&self.app_handle, request_id, command, payload, true).await
     }
 
-    fn try_direct_execution(
+    /// Same as [`execute_cli_command`](Self::execute_cli_command), but for
+    /// internal polling (e.g. `wait_until_stopped`'s status checks) that
+    /// should not surface as a `pb-output` event or occupy an
+    /// `ActiveCommands` cancellation slot.
+    async fn execute_cli_command_quiet(
         &self,
+        request_id: &str,
         command: &str,
         payload: Option<serde_json::Value>,
     ) -> Result<String, String> {
-        // Try to resolve the executable path directly
-        let exe_path = self.resolve_executable_path()?;
-
-        let mut cmd = Command::new(exe_path)
-            .arg(command)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to spawn process: {}", e))?;
-
-        // Send payload as JSON to stdin if provided
-        if let Some(payload) = payload {
-            if let Some(stdin) = cmd.stdin.as_mut() {
-                let payload_str = serde_json::to_string(&payload)
-                    .map_err(|e| format!("Failed to serialize payload: {}", e))?;
-                stdin
-                    .write_all(payload_str.as_bytes())
-                    .map_err(|e| format!("Failed to write to stdin: {}", e))?;
+        run_cli(&self.app_handle, request_id, command, payload, false).await
+    }
+
+    pub async fn initialize(&self) -> Result<String, String> {
+        // Use the CLI status command to initialize and check hardware. Not
+        // driven by `lib.rs::spawn_tracked` (this whole call is awaited to
+        // completion before `initialize_pulseblaster` resolves), so a fresh
+        // id is minted for it rather than one handed to the frontend.
+        let status = self
+            .execute_cli_command(&next_request_id(), "status", None)
+            .await?;
+
+        if let Some(instructions) = self.load_startup_program()? {
+            self.program_instructions(instructions, &next_request_id())
+                .await?;
+
+            let autostart = crate::config_store::get(&self.app_handle, "autostart")
+                .unwrap_or(None)
+                .map(|value| value == "true")
+                .unwrap_or(false);
+
+            if autostart {
+                self.start(&next_request_id()).await?;
             }
         }
 
-        let output = cmd
-            .wait_with_output()
-            .map_err(|e| format!("Failed to wait for command output: {}", e))?;
+        Ok(status)
+    }
+
+    /// Resolve the startup program to load, if any: an explicit
+    /// `startup_program` on the config takes priority; otherwise fall back
+    /// to the `startup` key persisted in the config store, so a sequence
+    /// saved from a previous launch is restored without the frontend having
+    /// to resend it every time.
+    fn load_startup_program(&self) -> Result<Option<Vec<PBInstruction>>, String> {
+        let startup_program = match self.config.startup_program.clone() {
+            Some(startup_program) => Some(startup_program),
+            None => crate::config_store::get(&self.app_handle, "startup")?.map(|stored| {
+                match serde_json::from_str::<Vec<PBInstruction>>(&stored) {
+                    Ok(instructions) => StartupProgram::Inline(instructions),
+                    Err(_) => StartupProgram::Path(stored),
+                }
+            }),
+        };
+
+        startup_program
+            .map(|startup_program| self.resolve_startup_program(startup_program))
+            .transpose()
+    }
 
-        if output.status.success() {
-            String::from_utf8(output.stdout).map_err(|e| format!("Invalid UTF-8 output: {}", e))
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(format!("Command failed: {}", stderr))
+    fn resolve_startup_program(
+        &self,
+        startup_program: StartupProgram,
+    ) -> Result<Vec<PBInstruction>, String> {
+        match startup_program {
+            StartupProgram::Inline(instructions) => Ok(instructions),
+            StartupProgram::Path(path) => {
+                let resolved_path = self.resolve_config_relative_path(&path)?;
+                let contents = std::fs::read_to_string(&resolved_path).map_err(|e| {
+                    format!(
+                        "Failed to read startup program {}: {}",
+                        resolved_path.display(),
+                        e
+                    )
+                })?;
+                serde_json::from_str(&contents).map_err(|e| {
+                    format!(
+                        "Failed to parse startup program {}: {}",
+                        resolved_path.display(),
+                        e
+                    )
+                })
+            }
         }
     }
 
-    fn resolve_executable_path(&self) -> Result<std::path::PathBuf, String> {
-        let exe_name = "pulseblaster.exe";
-        
-        // 1. Try Tauri resource resolution
-        if let Ok(resource_path) = self.app_handle
+    /// Resolve a startup program path against the app's config dir when
+    /// it's not already absolute, matching where `config_store` persists
+    /// `set_config`/`get_config` values.
+    fn resolve_config_relative_path(&self, path: &str) -> Result<std::path::PathBuf, String> {
+        let path = std::path::Path::new(path);
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+
+        let config_dir = self
+            .app_handle
             .path()
-            .resolve(&format!("bin/{}", exe_name), tauri::path::BaseDirectory::Resource) 
+            .app_config_dir()
+            .map_err(|e| format!("Failed to resolve app config dir: {}", e))?;
+
+        Ok(config_dir.join(path))
+    }
+
+    pub async fn program_instructions(
+        &self,
+        instructions: Vec<PBInstruction>,
+        request_id: &str,
+    ) -> Result<ProgramHandle, String> {
+        let instructions = self.normalize_durations(instructions)?;
+
+        // Validate against whichever clock was actually used to normalize
+        // the durations above: `clock_source` takes priority when set, and
+        // `core_clock_mhz` is only a fallback for the legacy path where no
+        // clock source is configured.
+        let effective_clock_mhz = self
+            .config
+            .clock_source
+            .map(|source| source.mhz())
+            .or(self.config.core_clock_mhz);
+
+        program_validator::validate_program(&instructions, effective_clock_mhz).map_err(|errors| {
+            serde_json::to_string(&errors).unwrap_or_else(|_| "program validation failed".to_string())
+        })?;
+
+        let instructions_json = serde_json::to_value(&instructions)
+            .map_err(|e| format!("Failed to serialize instructions: {}", e))?;
+        let handle = self.hash_program(&instructions_json);
+
         {
-            if resource_path.exists() {
-                return Ok(resource_path);
+            let loaded_handle = self.loaded_handle.lock().unwrap();
+            if loaded_handle.as_deref() == Some(handle.as_str()) {
+                return Ok(ProgramHandle {
+                    handle,
+                    reused: true,
+                });
             }
         }
 
-        // 2. Try relative to current executable (for bundled applications)
-        if let Ok(current_exe) = std::env::current_exe() {
-            if let Some(exe_dir) = current_exe.parent() {
-                let relative_path = exe_dir.join("bin").join(exe_name);
-                if relative_path.exists() {
-                    return Ok(relative_path);
-                }
-                
-                // Also try in the same directory as the main executable
-                let same_dir_path = exe_dir.join(exe_name);
-                if same_dir_path.exists() {
-                    return Ok(same_dir_path);
-                }
-            }
-        }
+        let payload = serde_json::json!({
+            "board": self.config.board,
+            // The clock actually used to normalize the durations above, not
+            // the raw `core_clock_mhz` field — otherwise the CLI receives
+            // tick counts with no clock (or the wrong one) to interpret them
+            // against whenever `clock_source` is what's configured.
+            "coreClockMHz": effective_clock_mhz,
+            "clockSource": self.config.clock_source,
+            "debug": self.config.debug,
+            "program": instructions_json
+        });
 
-        // 3. Try from src-tauri directory (development)
-        let src_tauri_path = std::path::Path::new("src-tauri")
-            .join("bin")
-            .join(exe_name);
-        if src_tauri_path.exists() {
-            return Ok(src_tauri_path);
-        }
+        self.execute_cli_command(request_id, "run", Some(payload)).await?;
+        *self.loaded_handle.lock().unwrap() = Some(handle.clone());
 
-        // 4. Try from current working directory + src-tauri (for when running from project root)
-        if let Ok(current_dir) = std::env::current_dir() {
-            let cwd_path = current_dir.join("src-tauri").join("bin").join(exe_name);
-            if cwd_path.exists() {
-                return Ok(cwd_path);
+        Ok(ProgramHandle {
+            handle,
+            reused: false,
+        })
+    }
+
+    /// Start the currently loaded program, asserting that `handle` still
+    /// matches what's loaded on the board (i.e. nothing reprogrammed it out
+    /// from under the caller since `program_instructions` returned it).
+    pub async fn replay_program(&self, handle: &str, request_id: &str) -> Result<String, String> {
+        {
+            let loaded_handle = self.loaded_handle.lock().unwrap();
+            if loaded_handle.as_deref() != Some(handle) {
+                return Err(
+                    "Program handle is stale: the board was reprogrammed since it was loaded"
+                        .to_string(),
+                );
             }
         }
 
-        Err("Could not locate pulseblaster.exe executable".to_string())
+        self.start(request_id).await
     }
 
-    pub fn initialize(&self) -> Result<String, String> {
-        // Use the CLI status command to initialize and check hardware
-        self.execute_cli_command("status", None)
+    /// Rewrite every instruction's duration into ticks of the configured
+    /// clock source, so timing is deterministic regardless of which units
+    /// the frontend authored the program in. Rejects durations that aren't
+    /// an integer number of clock periods. A no-op when no clock source is
+    /// configured (unit conversion is then left to the CLI, as before).
+    fn normalize_durations(
+        &self,
+        mut instructions: Vec<PBInstruction>,
+    ) -> Result<Vec<PBInstruction>, String> {
+        let Some(clock_source) = self.config.clock_source else {
+            return Ok(instructions);
+        };
+
+        let period_ns = 1_000.0 / clock_source.mhz();
+
+        for instruction in instructions.iter_mut() {
+            let duration_ns = program_validator::duration_to_ns(
+                instruction.duration,
+                &instruction.units,
+                Some(clock_source.mhz()),
+            )?;
+
+            let ticks = duration_ns / period_ns;
+            // Absolute tolerance alone breaks down for large tick counts
+            // (float rounding error grows with the magnitude of `ticks`, so
+            // a fixed 1e-6 eventually rejects valid long delays) — scale the tolerance to the
+            // magnitude of `ticks` itself, floored at the old constant so
+            // small values aren't validated any more loosely than before.
+            let tolerance = (ticks.abs() * 1e-9).max(1e-6);
+            if (ticks - ticks.round()).abs() > tolerance {
+                return Err(format!(
+                    "duration {}{} is not an integer multiple of the {}MHz clock period ({}ns)",
+                    instruction.duration,
+                    instruction.units,
+                    clock_source.mhz(),
+                    period_ns
+                ));
+            }
+
+            instruction.duration = ticks.round();
+            instruction.units = "clk".to_string();
+        }
+
+        Ok(instructions)
     }
 
-    pub fn program_instructions(&self, instructions: Vec<PBInstruction>) -> Result<String, String> {
-        let payload = serde_json::json!({
+    /// Compute a stable content hash over the instruction sequence plus the
+    /// board and core clock it was compiled for, so a cached handle is only
+    /// reused when both the program and the hardware context match.
+    fn hash_program(&self, instructions_json: &serde_json::Value) -> String {
+        let canonical = serde_json::json!({
             "board": self.config.board,
-            "coreClockMHz": self.config.core_clock_mhz,
-            "debug": self.config.debug,
-            "program": instructions
+            "coreClockMHz": self
+                .config
+                .clock_source
+                .map(|source| source.mhz())
+                .or(self.config.core_clock_mhz),
+            "program": instructions_json
         });
 
-        self.execute_cli_command("run", Some(payload))
+        let mut hasher = Sha256::new();
+        hasher.update(canonical.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
     }
 
-    pub fn start(&self) -> Result<String, String> {
-        self.execute_cli_command("start", None)
+    pub async fn start(&self, request_id: &str) -> Result<String, String> {
+        self.execute_cli_command(request_id, "start", None).await
     }
 
-    pub fn stop(&self) -> Result<String, String> {
-        self.execute_cli_command("stop", None)
+    pub async fn stop(&self, request_id: &str) -> Result<String, String> {
+        self.execute_cli_command(request_id, "stop", None).await
     }
 
-    pub fn reset(&self) -> Result<String, String> {
-        self.execute_cli_command("reset", None)
+    pub async fn reset(&self, request_id: &str) -> Result<String, String> {
+        self.execute_cli_command(request_id, "reset", None).await
     }
 
-    pub fn get_status(&self) -> Result<String, String> {
-        self.execute_cli_command("status", None)
+    pub async fn get_status(&self, request_id: &str) -> Result<String, String> {
+        self.execute_cli_command(request_id, "status", None).await
     }
 
-    pub fn wait_until_stopped(&self, timeout_s: f64) -> Result<String, String> {
-        let payload = serde_json::json!({
-            "timeout_s": timeout_s
-        });
-        self.execute_cli_command("wait", Some(payload))
-    }
+    /// Poll `status` until the board reports it has stopped running, or
+    /// `timeout_s` elapses, emitting a `pb-status` event after every poll so
+    /// the frontend sees progress instead of a single blocking wait.
+    ///
+    /// Registers itself in `ActiveCommands` under `request_id` for the
+    /// duration of the wait (not just the individual, quiet status polls),
+    /// so `cancel_command` can stop a long or hung wait the same way it
+    /// cancels a CLI child process.
+    pub async fn wait_until_stopped(&self, timeout_s: f64, request_id: &str) -> Result<String, String> {
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs_f64(timeout_s);
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        {
+            let state = self.app_handle.state::<ActiveCommands>();
+            state.0.lock().unwrap().insert(
+                request_id.to_string(),
+                ActiveCommand::Cancellable(cancelled.clone()),
+            );
+        }
 
+        let outcome = loop {
+            if cancelled.load(Ordering::Relaxed) {
+                break Err("Wait was cancelled".to_string());
+            }
+
+            let status = match self.execute_cli_command_quiet(request_id, "status", None).await {
+                Ok(status) => status,
+                Err(err) => break Err(err),
+            };
+            let _ = self.app_handle.emit("pb-status", &status);
+
+            if status.to_lowercase().contains("stopped") {
+                break Ok(status);
+            }
 
+            if std::time::Instant::now() >= deadline {
+                break Err(format!(
+                    "Timed out after {}s waiting for board to stop",
+                    timeout_s
+                ));
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        };
+
+        {
+            let state = self.app_handle.state::<ActiveCommands>();
+            state.0.lock().unwrap().remove(request_id);
+        }
+
+        outcome
+    }
 }